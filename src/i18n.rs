@@ -0,0 +1,289 @@
+//! Optional localization support: a [`Localizations`] catalog store
+//! that registers a `translate` [`Tera`] filter and selects a locale
+//! from a request's `Accept-Language` header.
+
+use async_std::sync::Arc;
+use std::collections::HashMap;
+use std::path::Path;
+use tera::{Context, Tera, Value};
+
+/// The context key under which the selected locale is stored by
+/// [`Localizations::select_from_header`].
+pub const LANG_KEY: &str = "__lang__";
+
+/// A store of per-locale message catalogs loaded from a directory, one
+/// JSON file per locale (e.g. `en.json`, `fr.json`), each mapping
+/// message keys to translated strings.
+///
+/// Registering the store with [`register`](Localizations::register)
+/// adds a `translate` filter so templates can write
+/// `{{ "greeting" | translate(lang=__lang__) }}` and get the text for
+/// the locale chosen by [`select_from_header`](Localizations::select_from_header),
+/// falling back to the default locale when the locale or key is missing.
+/// Any additional arguments are interpolated into the matched message
+/// using Tera's `{{ name }}` syntax.
+///
+/// # Passing the locale
+///
+/// Tera filters only receive `(value, args)` — they cannot read context
+/// variables — so the bare `{{ "greeting" | translate }}` form cannot
+/// observe the negotiated locale and always resolves against the default
+/// locale. To honour the request's `Accept-Language` selection, templates
+/// must thread the locale through explicitly as
+/// `{{ "greeting" | translate(lang=__lang__) }}`, where `__lang__` is the
+/// [`LANG_KEY`] value that [`select_from_header`](Localizations::select_from_header)
+/// writes into the context. Keeping the locale an explicit argument also
+/// makes each render self-contained, with no shared per-request state.
+#[derive(Debug, Clone)]
+pub struct Localizations {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    catalogs: HashMap<String, HashMap<String, String>>,
+    default_locale: String,
+}
+
+impl Localizations {
+    /// Load every `*.json` file in `dir` as a locale catalog named after
+    /// its file stem, using `default_locale` for fallback.
+    pub fn from_dir(dir: impl AsRef<Path>, default_locale: &str) -> tera::Result<Self> {
+        let mut catalogs = HashMap::new();
+
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| tera::Error::msg(format!("could not read localization dir: {}", e)))?;
+
+        for entry in entries {
+            let path = entry
+                .map_err(|e| tera::Error::msg(e.to_string()))?
+                .path();
+
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            let locale = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(locale) => locale.to_string(),
+                None => continue,
+            };
+
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| tera::Error::msg(e.to_string()))?;
+            let catalog: HashMap<String, String> = serde_json::from_str(&contents)
+                .map_err(|e| tera::Error::msg(format!("invalid catalog {:?}: {}", path, e)))?;
+
+            catalogs.insert(locale, catalog);
+        }
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                catalogs,
+                default_locale: default_locale.to_string(),
+            }),
+        })
+    }
+
+    /// Register the `translate` filter on a [`Tera`] instance.
+    pub fn register(&self, tera: &mut Tera) {
+        let inner = self.inner.clone();
+        tera.register_filter(
+            "translate",
+            move |value: &Value, args: &HashMap<String, Value>| {
+                let key = value
+                    .as_str()
+                    .ok_or_else(|| tera::Error::msg("translate expects a string key"))?;
+
+                let lang = args
+                    .get("lang")
+                    .and_then(Value::as_str)
+                    .unwrap_or(&inner.default_locale);
+
+                let message = inner.lookup(lang, key);
+                Ok(Value::String(interpolate(&message, args)))
+            },
+        );
+    }
+
+    /// Parse the `Accept-Language` header, pick the best catalog locale
+    /// (falling back first to the primary language subtag, then to the
+    /// default locale), and store it in `context` under [`LANG_KEY`] so
+    /// templates can pass it to the `translate` filter. Returns the
+    /// chosen locale.
+    pub fn select_from_header(&self, header: Option<&str>, context: &mut Context) -> String {
+        let locale = header
+            .map(parse_accept_language)
+            .unwrap_or_default()
+            .into_iter()
+            .find_map(|locale| self.best_match(&locale))
+            .unwrap_or_else(|| self.inner.default_locale.clone());
+
+        context.insert(LANG_KEY, &locale);
+        locale
+    }
+
+    /// Resolve a requested locale to a catalog name, trying the full tag
+    /// (e.g. `en-us`) before its primary language subtag (`en`).
+    fn best_match(&self, locale: &str) -> Option<String> {
+        if self.inner.catalogs.contains_key(locale) {
+            return Some(locale.to_string());
+        }
+        let base = locale.split('-').next()?;
+        if base != locale && self.inner.catalogs.contains_key(base) {
+            return Some(base.to_string());
+        }
+        None
+    }
+}
+
+impl Inner {
+    fn lookup(&self, locale: &str, key: &str) -> String {
+        self.catalogs
+            .get(locale)
+            .and_then(|catalog| catalog.get(key))
+            .or_else(|| {
+                self.catalogs
+                    .get(&self.default_locale)
+                    .and_then(|catalog| catalog.get(key))
+            })
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+}
+
+/// Replace `{{ name }}` (or `{{name}}`) placeholders in `message` with
+/// the matching filter arguments, ignoring the reserved `lang`
+/// argument. String arguments are inserted as-is; other values are
+/// inserted in their JSON form.
+fn interpolate(message: &str, args: &HashMap<String, Value>) -> String {
+    let mut out = message.to_string();
+    for (name, value) in args {
+        if name == "lang" {
+            continue;
+        }
+        let rendered = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        out = out.replace(&format!("{{{{ {} }}}}", name), &rendered);
+        out = out.replace(&format!("{{{{{}}}}}", name), &rendered);
+    }
+    out
+}
+
+/// Parse an `Accept-Language` header value into a list of locale codes
+/// ordered by descending quality weight. Tags are lowercased; region
+/// subtags are preserved so callers can fall back to the primary
+/// language themselves (see [`Localizations::select_from_header`]).
+pub fn parse_accept_language(header: &str) -> Vec<String> {
+    let mut weighted: Vec<(f32, String)> = header
+        .split(',')
+        .filter_map(|part| {
+            let mut pieces = part.split(';');
+            let locale = pieces.next()?.trim();
+            if locale.is_empty() || locale == "*" {
+                return None;
+            }
+
+            let quality = pieces
+                .find_map(|p| p.trim().strip_prefix("q=").and_then(|q| q.parse::<f32>().ok()))
+                .unwrap_or(1.0);
+
+            if quality <= 0.0 {
+                return None;
+            }
+
+            Some((quality, locale.to_ascii_lowercase()))
+        })
+        .collect();
+
+    weighted.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    weighted.into_iter().map(|(_, locale)| locale).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn localizations() -> Localizations {
+        let mut catalogs = HashMap::new();
+        catalogs.insert(
+            "en".to_string(),
+            [("greeting", "hello {{ name }}"), ("bye", "goodbye")]
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        );
+        catalogs.insert(
+            "fr".to_string(),
+            [("greeting", "bonjour {{ name }}")]
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        );
+
+        Localizations {
+            inner: Arc::new(Inner {
+                catalogs,
+                default_locale: "en".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn lookup_falls_back_to_default_locale() {
+        let loc = localizations();
+        // key present only in the default locale
+        assert_eq!(loc.inner.lookup("fr", "bye"), "goodbye");
+        // missing key returns the key itself
+        assert_eq!(loc.inner.lookup("fr", "missing"), "missing");
+    }
+
+    #[test]
+    fn parse_accept_language_orders_by_quality() {
+        let parsed = parse_accept_language("fr;q=0.8, en-US, de;q=0");
+        assert_eq!(parsed, vec!["en-us".to_string(), "fr".to_string()]);
+    }
+
+    #[test]
+    fn select_falls_back_to_primary_subtag() {
+        let loc = localizations();
+        let mut context = Context::new();
+        let chosen = loc.select_from_header(Some("en-US"), &mut context);
+        assert_eq!(chosen, "en");
+        assert_eq!(context.into_json()[LANG_KEY], "en");
+    }
+
+    #[test]
+    fn select_falls_back_to_default_when_unmatched() {
+        let loc = localizations();
+        let mut context = Context::new();
+        assert_eq!(loc.select_from_header(Some("es"), &mut context), "en");
+        assert_eq!(loc.select_from_header(None, &mut context), "en");
+    }
+
+    #[test]
+    fn translate_filter_uses_lang_argument_and_interpolates() {
+        let loc = localizations();
+        let mut tera = Tera::default();
+        loc.register(&mut tera);
+        tera.add_raw_template("t", r#"{{ "greeting" | translate(lang=__lang__, name="tide") }}"#)
+            .unwrap();
+
+        let mut context = Context::new();
+        context.insert(LANG_KEY, "fr");
+        assert_eq!(tera.render("t", &context).unwrap(), "bonjour tide");
+
+        context.insert(LANG_KEY, "en");
+        assert_eq!(tera.render("t", &context).unwrap(), "hello tide");
+    }
+
+    #[test]
+    fn translate_filter_defaults_to_default_locale() {
+        let loc = localizations();
+        let mut tera = Tera::default();
+        loc.register(&mut tera);
+        tera.add_raw_template("t", r#"{{ "bye" | translate }}"#).unwrap();
+        assert_eq!(tera.render("t", &Context::new()).unwrap(), "goodbye");
+    }
+}
@@ -5,9 +5,15 @@
 //! convenience [`context`] macro for creating ad-hoc tera
 //! [`Context`](tera::Context)s.
 
+use serde::Serialize;
+use std::convert::TryInto;
 use std::path::PathBuf;
 use tera::{Context, Tera};
-use tide::{http::Mime, Body, Response, Result};
+use tide::{http::Mime, Body, Response, ResponseBuilder, Result, StatusCode};
+
+pub mod i18n;
+mod middleware;
+pub use middleware::{TeraHandle, TeraMiddleware, TideTeraRequestExt};
 
 /// This extension trait adds two methods to [`tera::Tera`]:
 /// [`render_response`](TideTeraExt::render_response) and
@@ -27,6 +33,85 @@ pub trait TideTeraExt {
     /// assert_eq!(response.content_type(), Some(tide::http::mime::HTML));
     ///```
     fn render_response(&self, template_name: &str, context: &Context) -> Result;
+
+    /// `render_response_with_status` is like
+    /// [`render_response`](TideTeraExt::render_response) but sets the
+    /// given status code instead of always returning `200`. This will
+    /// return an `Err` variant if the render was unsuccessful.
+    ///
+    /// ```rust
+    /// use tide_tera::prelude::*;
+    /// let tera = tera::Tera::new("tests/templates/**/*").unwrap();
+    /// let response = tera
+    ///     .render_response_with_status(201, "good_template.html", &context! { "name" => "tide" })
+    ///     .unwrap();
+    /// assert_eq!(response.status(), tide::http::StatusCode::Created);
+    ///```
+    fn render_response_with_status(
+        &self,
+        status: impl TryInto<StatusCode>,
+        template_name: &str,
+        context: &Context,
+    ) -> Result;
+
+    /// `render_response_builder` returns a [`tide::ResponseBuilder`]
+    /// pre-populated with the given status and the rendered body (with
+    /// its extension-derived MIME type), so handlers can chain
+    /// [`header`](tide::ResponseBuilder::header) calls before building
+    /// the final [`Response`]. This will return an `Err` variant if the
+    /// render was unsuccessful.
+    ///
+    /// ```rust
+    /// use tide_tera::prelude::*;
+    /// let tera = tera::Tera::new("tests/templates/**/*").unwrap();
+    /// let response = tera
+    ///     .render_response_builder(200, "good_template.html", &context! { "name" => "tide" })
+    ///     .unwrap()
+    ///     .header("x-powered-by", "tide-tera")
+    ///     .build();
+    /// assert_eq!(response.content_type(), Some(tide::http::mime::HTML));
+    ///```
+    fn render_response_builder(
+        &self,
+        status: impl TryInto<StatusCode>,
+        template_name: &str,
+        context: &Context,
+    ) -> Result<ResponseBuilder>;
+
+    /// `render_error` renders a template at the given status code,
+    /// intended to be wired into a tide error-handling middleware so
+    /// that, for example, a `404` renders `errors/404.html` and a `500`
+    /// renders `errors/500.html`. It is a thin wrapper over
+    /// [`render_response_with_status`](TideTeraExt::render_response_with_status)
+    /// that names the error intent at the call site.
+    fn render_error(
+        &self,
+        status: impl TryInto<StatusCode>,
+        template_name: &str,
+        context: &Context,
+    ) -> Result;
+
+    /// `render_response_ser` is like
+    /// [`render_response`](TideTeraExt::render_response) but accepts any
+    /// [`serde::Serialize`] value as the render context, building a
+    /// [`tera::Context`] from it with
+    /// [`Context::from_serialize`](tera::Context::from_serialize). This
+    /// is convenient when a handler already has a view-model struct. It
+    /// will return an `Err` variant if the value does not serialize to a
+    /// map or if the render was unsuccessful.
+    ///
+    /// ```rust
+    /// use tide_tera::prelude::*;
+    /// use serde::Serialize;
+    /// #[derive(Serialize)]
+    /// struct Greeting { name: &'static str }
+    /// let tera = tera::Tera::new("tests/templates/**/*").unwrap();
+    /// let response = tera
+    ///     .render_response_ser("good_template.html", &Greeting { name: "tide" })
+    ///     .unwrap();
+    /// assert_eq!(response.content_type(), Some(tide::http::mime::HTML));
+    ///```
+    fn render_response_ser<S: Serialize>(&self, template_name: &str, data: &S) -> Result;
     /// `render_response` returns a tide Response with a body rendered
     /// with [`render_body`](TideTeraExt::render_body). This will
     /// return an `Err` variant if the render was unsuccessful.
@@ -40,6 +125,58 @@ pub trait TideTeraExt {
     /// assert_eq!(body.mime(), &tide::http::mime::HTML);
     ///```
     fn render_body(&self, template_name: &str, context: &Context) -> Result<Body>;
+
+    /// `has_template` reports whether a template with the given name is
+    /// registered, so callers can avoid the `Err` churn of rendering a
+    /// name that was never loaded.
+    ///
+    /// ```rust
+    /// use tide_tera::prelude::*;
+    /// let tera = tera::Tera::new("tests/templates/**/*").unwrap();
+    /// assert!(tera.has_template("good_template.html"));
+    /// assert!(!tera.has_template("nonexistent.html"));
+    ///```
+    fn has_template(&self, template_name: &str) -> bool;
+
+    /// `template_names` returns the names of every registered template.
+    fn template_names(&self) -> Vec<&str>;
+
+    /// `render_negotiated` picks the registered template variant that
+    /// best matches the request's `Accept` header from the set of
+    /// templates sharing `base_name` but differing by extension (e.g.
+    /// `home.html`, `home.json`, `home.txt`), renders it, and sets the
+    /// MIME type from the chosen extension with the same logic as
+    /// [`render_body`](TideTeraExt::render_body). This lets one route
+    /// serve HTML to browsers and JSON or plain text to API clients from
+    /// parallel templates. It will return an `Err` variant if no variant
+    /// matches the `Accept` header or if the render was unsuccessful.
+    fn render_negotiated(
+        &self,
+        base_name: &str,
+        accept: &str,
+        context: &Context,
+    ) -> Result;
+
+    /// `render_body_ser` is like
+    /// [`render_body`](TideTeraExt::render_body) but accepts any
+    /// [`serde::Serialize`] value as the render context, building a
+    /// [`tera::Context`] from it with
+    /// [`Context::from_serialize`](tera::Context::from_serialize). This
+    /// will return an `Err` variant if the value does not serialize to a
+    /// map or if the render was unsuccessful.
+    ///
+    /// ```rust
+    /// use tide_tera::prelude::*;
+    /// use serde::Serialize;
+    /// #[derive(Serialize)]
+    /// struct Greeting { name: &'static str }
+    /// let tera = tera::Tera::new("tests/templates/**/*").unwrap();
+    /// let body = tera
+    ///     .render_body_ser("good_template.html", &Greeting { name: "tide" })
+    ///     .unwrap();
+    /// assert_eq!(body.mime(), &tide::http::mime::HTML);
+    ///```
+    fn render_body_ser<S: Serialize>(&self, template_name: &str, data: &S) -> Result<Body>;
 }
 
 impl TideTeraExt for Tera {
@@ -62,6 +199,159 @@ impl TideTeraExt for Tera {
         response.set_body(self.render_body(template_name, context)?);
         Ok(response)
     }
+
+    fn render_response_with_status(
+        &self,
+        status: impl TryInto<StatusCode>,
+        template_name: &str,
+        context: &Context,
+    ) -> Result {
+        let status: StatusCode = status.try_into().map_err(|_| {
+            tide::Error::from_str(StatusCode::InternalServerError, "invalid status code")
+        })?;
+        let mut response = Response::new(status);
+        response.set_body(self.render_body(template_name, context)?);
+        Ok(response)
+    }
+
+    fn render_response_builder(
+        &self,
+        status: impl TryInto<StatusCode>,
+        template_name: &str,
+        context: &Context,
+    ) -> Result<ResponseBuilder> {
+        let status: StatusCode = status.try_into().map_err(|_| {
+            tide::Error::from_str(StatusCode::InternalServerError, "invalid status code")
+        })?;
+        let body = self.render_body(template_name, context)?;
+        Ok(Response::builder(status).body(body))
+    }
+
+    fn render_error(
+        &self,
+        status: impl TryInto<StatusCode>,
+        template_name: &str,
+        context: &Context,
+    ) -> Result {
+        self.render_response_with_status(status, template_name, context)
+    }
+
+    fn has_template(&self, template_name: &str) -> bool {
+        self.get_template_names().any(|name| name == template_name)
+    }
+
+    fn template_names(&self) -> Vec<&str> {
+        self.get_template_names().collect()
+    }
+
+    fn render_negotiated(&self, base_name: &str, accept: &str, context: &Context) -> Result {
+        // collect the registered `base_name.<ext>` variants alongside
+        // the MIME type their extension maps to
+        let variants: Vec<(&str, Mime)> = self
+            .get_template_names()
+            .filter_map(|name| {
+                let path = PathBuf::from(name);
+                if path.with_extension("").to_str() != Some(base_name) {
+                    return None;
+                }
+                let extension = path.extension()?.to_string_lossy().into_owned();
+                let mime = Mime::from_extension(extension)?;
+                Some((name, mime))
+            })
+            .collect();
+
+        let chosen = negotiate(accept, &variants).ok_or_else(|| {
+            tide::Error::from_str(
+                StatusCode::NotAcceptable,
+                format!("no variant of `{}` matches the Accept header", base_name),
+            )
+        })?;
+
+        self.render_response(chosen, context)
+    }
+
+    fn render_body_ser<S: Serialize>(&self, template_name: &str, data: &S) -> Result<Body> {
+        let context = Context::from_serialize(data)?;
+        self.render_body(template_name, &context)
+    }
+
+    fn render_response_ser<S: Serialize>(&self, template_name: &str, data: &S) -> Result {
+        let context = Context::from_serialize(data)?;
+        self.render_response(template_name, &context)
+    }
+}
+
+/// Pick the template variant whose MIME type best satisfies the
+/// `Accept` header, honouring quality weights and `type/*` / `*/*`
+/// wildcards. Returns the matching template name, or `None` when no
+/// variant is acceptable.
+///
+/// Ranges are considered by descending quality and, at equal quality, by
+/// descending specificity (a concrete `type/subtype` before `type/*`
+/// before `*/*`, per RFC 7231). When several variants match the chosen
+/// range, the lexicographically-smallest template name wins, so a
+/// route's default representation is stable regardless of `Tera`'s
+/// `HashMap` iteration order.
+fn negotiate<'a>(accept: &str, variants: &[(&'a str, Mime)]) -> Option<&'a str> {
+    let mut ranges: Vec<(f32, u8, &str)> = accept
+        .split(',')
+        .filter_map(|part| {
+            let mut pieces = part.split(';');
+            let range = pieces.next()?.trim();
+            if range.is_empty() {
+                return None;
+            }
+            let quality = pieces
+                .find_map(|p| p.trim().strip_prefix("q=").and_then(|q| q.parse::<f32>().ok()))
+                .unwrap_or(1.0);
+            // a range with q=0 means "not acceptable"
+            if quality <= 0.0 {
+                return None;
+            }
+            Some((quality, specificity(range), range))
+        })
+        .collect();
+
+    ranges.sort_by(|a, b| {
+        b.0.partial_cmp(&a.0)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.1.cmp(&a.1))
+    });
+
+    for (_, _, range) in ranges {
+        let mut matches: Vec<&str> = variants
+            .iter()
+            .filter(|(_, mime)| range_matches(range, mime))
+            .map(|(name, _)| *name)
+            .collect();
+        matches.sort_unstable();
+        if let Some(name) = matches.into_iter().next() {
+            return Some(name);
+        }
+    }
+
+    None
+}
+
+/// RFC 7231 specificity of an `Accept` media range: concrete
+/// `type/subtype` (2) is preferred over `type/*` (1) over `*/*` (0).
+fn specificity(range: &str) -> u8 {
+    match range {
+        "*/*" => 0,
+        range if range.ends_with("/*") => 1,
+        _ => 2,
+    }
+}
+
+/// Whether a single `Accept` media range matches a concrete [`Mime`].
+fn range_matches(range: &str, mime: &Mime) -> bool {
+    match range {
+        "*/*" => true,
+        range => match range.split_once('/') {
+            Some((type_, "*")) => type_.eq_ignore_ascii_case(mime.basetype()),
+            _ => range.eq_ignore_ascii_case(mime.essence()),
+        },
+    }
 }
 
 /// this macro simplifies creation of ad-hoc [`tera::Context`]s.
@@ -87,7 +377,7 @@ macro_rules! context {
 
 pub mod prelude {
     //! exposes [`context`] and [`TideTeraExt].
-    pub use super::{context, TideTeraExt};
+    pub use super::{context, TideTeraExt, TeraMiddleware, TideTeraRequestExt};
 }
 
 #[cfg(test)]
@@ -135,6 +425,77 @@ mod tests {
         assert_eq!(body_string, "hello tide!\n");
     }
 
+    #[async_std::test]
+    async fn test_body_ser() {
+        #[derive(serde::Serialize)]
+        struct Greeting {
+            name: &'static str,
+        }
+
+        let tera = Tera::new("tests/templates/**/*").unwrap();
+        let mut body = tera
+            .render_body_ser("good_template.html", &Greeting { name: "tide" })
+            .unwrap();
+
+        assert_eq!(body.mime(), &tide::http::mime::HTML);
+
+        let mut body_string = String::new();
+        body.read_to_string(&mut body_string).await.unwrap();
+        assert_eq!(body_string, "hello tide!\n");
+    }
+
+    #[async_std::test]
+    async fn response_with_status() {
+        let tera = Tera::new("tests/templates/**/*").unwrap();
+        let response = tera
+            .render_response_with_status(404, "good_template.html", &context! { "name" => "tide" })
+            .unwrap();
+
+        assert_eq!(response.status(), tide::http::StatusCode::NotFound);
+        assert_eq!(response.content_type(), Some(tide::http::mime::HTML));
+    }
+
+    #[test]
+    fn has_template() {
+        let tera = Tera::new("tests/templates/**/*").unwrap();
+        assert!(tera.has_template("good_template.html"));
+        assert!(!tera.has_template("nonexistent.html"));
+        assert!(tera.template_names().contains(&"good_template.html"));
+    }
+
+    #[async_std::test]
+    async fn negotiated() {
+        let tera = Tera::new("tests/templates/**/*").unwrap();
+        let response = tera
+            .render_negotiated("good_template", "text/html, */*;q=0.1", &context! { "name" => "tide" })
+            .unwrap();
+
+        assert_eq!(response.content_type(), Some(tide::http::mime::HTML));
+    }
+
+    #[test]
+    fn negotiate_excludes_q_zero() {
+        let html = Mime::from_extension("html").unwrap();
+        let variants = [("home.html", html)];
+        assert_eq!(negotiate("text/html", &variants), Some("home.html"));
+        assert_eq!(negotiate("text/html;q=0", &variants), None);
+    }
+
+    #[test]
+    fn negotiate_is_deterministic() {
+        let variants = [
+            ("home.json", Mime::from_extension("json").unwrap()),
+            ("home.html", Mime::from_extension("html").unwrap()),
+        ];
+        // a concrete range wins over a wildcard of equal quality
+        assert_eq!(
+            negotiate("text/html, */*", &variants),
+            Some("home.html")
+        );
+        // wildcard matches the lexicographically-smallest variant name
+        assert_eq!(negotiate("*/*", &variants), Some("home.html"));
+    }
+
     #[test]
     fn unknown_content_type() {
         let tera = Tera::new("tests/templates/**/*").unwrap();
@@ -0,0 +1,157 @@
+use crate::TideTeraExt;
+use async_std::sync::{Arc, RwLock};
+use tera::{Context, Tera};
+use tide::{Body, Middleware, Next, Request, Response, Result};
+
+/// A [`tide::Middleware`] that injects a shared [`Tera`] instance into
+/// each request's extensions so that handlers can render templates
+/// regardless of the application's own state type.
+///
+/// Unlike using [`tide::with_state`] with a bare [`Tera`], this keeps
+/// the templates behind an `Arc<RwLock<Tera>>`, which makes it possible
+/// to reload them at runtime. When the `hot-reload` feature is enabled
+/// and the middleware is built with [`TeraMiddleware::watch`], a
+/// filesystem watcher calls [`Tera::full_reload`] whenever a file
+/// matching the construction glob changes, so template edits show up on
+/// the next request without a rebuild.
+///
+/// ```rust
+/// use tide_tera::prelude::*;
+/// let tera = tera::Tera::new("tests/templates/**/*").unwrap();
+/// let mut app = tide::new();
+/// app.with(TeraMiddleware::new(tera));
+/// app.at("/:name").get(|req: tide::Request<()>| async move {
+///     let name = req.param("name")?.to_string();
+///     req.tera()
+///         .render_response("good_template.html", &context! { "name" => name })
+///         .await
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct TeraMiddleware {
+    tera: Arc<RwLock<Tera>>,
+}
+
+impl TeraMiddleware {
+    /// Build a middleware around an already-constructed [`Tera`].
+    pub fn new(tera: Tera) -> Self {
+        Self {
+            tera: Arc::new(RwLock::new(tera)),
+        }
+    }
+
+    /// Build a middleware from a template glob, spawning a filesystem
+    /// watcher that reloads the templates on change.
+    ///
+    /// Only available with the `hot-reload` feature. The watcher runs on
+    /// its own background thread for the rest of the process; dropping
+    /// the returned middleware does not stop it.
+    #[cfg(feature = "hot-reload")]
+    pub fn watch(glob: &str) -> tera::Result<Self> {
+        let tera = Arc::new(RwLock::new(Tera::new(glob)?));
+        spawn_watcher(glob.to_string(), tera.clone());
+        Ok(Self { tera })
+    }
+
+    /// A cheap, cloneable handle to the shared [`Tera`] instance, for
+    /// use outside of the request lifecycle.
+    pub fn handle(&self) -> TeraHandle {
+        TeraHandle(self.tera.clone())
+    }
+}
+
+#[tide::utils::async_trait]
+impl<State: Clone + Send + Sync + 'static> Middleware<State> for TeraMiddleware {
+    async fn handle(&self, mut req: Request<State>, next: Next<'_, State>) -> Result {
+        req.set_ext(self.handle());
+        Ok(next.run(req).await)
+    }
+}
+
+/// A cloneable handle to the [`Tera`] instance injected by
+/// [`TeraMiddleware`]. Obtained from a request with
+/// [`TideTeraRequestExt::tera`].
+///
+/// The render methods mirror those on the [`TideTeraExt`] trait, taking
+/// a read lock on the shared instance for the duration of the render.
+#[derive(Debug, Clone)]
+pub struct TeraHandle(Arc<RwLock<Tera>>);
+
+impl TeraHandle {
+    /// Render a template into a [`tide::Body`], see
+    /// [`TideTeraExt::render_body`].
+    pub async fn render_body(&self, template_name: &str, context: &Context) -> Result<Body> {
+        self.0.read().await.render_body(template_name, context)
+    }
+
+    /// Render a template into a [`tide::Response`], see
+    /// [`TideTeraExt::render_response`].
+    pub async fn render_response(&self, template_name: &str, context: &Context) -> Result<Response> {
+        self.0.read().await.render_response(template_name, context)
+    }
+
+    /// Reload all templates under the write lock, see
+    /// [`Tera::full_reload`].
+    pub async fn full_reload(&self) -> tera::Result<()> {
+        self.0.write().await.full_reload()
+    }
+}
+
+/// Adds a [`tera`](TideTeraRequestExt::tera) accessor to
+/// [`tide::Request`] for retrieving the [`TeraHandle`] inserted by
+/// [`TeraMiddleware`].
+pub trait TideTeraRequestExt {
+    /// The [`TeraHandle`] injected by [`TeraMiddleware`].
+    ///
+    /// Panics if [`TeraMiddleware`] is not installed on the app.
+    fn tera(&self) -> &TeraHandle;
+}
+
+impl<State: Clone + Send + Sync + 'static> TideTeraRequestExt for Request<State> {
+    fn tera(&self) -> &TeraHandle {
+        self.ext()
+            .expect("TeraMiddleware must be installed to call Request::tera")
+    }
+}
+
+#[cfg(feature = "hot-reload")]
+fn spawn_watcher(glob: String, tera: Arc<RwLock<Tera>>) {
+    use notify::{RecursiveMode, Watcher};
+    use std::path::Path;
+
+    // derive the directory to watch from the leading, non-glob portion
+    // of the pattern
+    let root = glob
+        .split(|c| c == '*' || c == '?' || c == '[')
+        .next()
+        .filter(|s| !s.is_empty())
+        .unwrap_or(".")
+        .to_string();
+
+    // the notify watcher and its std mpsc receiver are both blocking, so
+    // they live on a dedicated thread rather than parking an async-std
+    // executor worker
+    std::thread::spawn(move || {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut watcher = match notify::recommended_watcher(tx) {
+            Ok(watcher) => watcher,
+            Err(error) => {
+                tide::log::error!("tera watcher failed to start", { error: error.to_string() });
+                return;
+            }
+        };
+
+        if let Err(error) = watcher.watch(Path::new(&root), RecursiveMode::Recursive) {
+            tide::log::error!("tera watcher failed to watch", { error: error.to_string() });
+            return;
+        }
+
+        for event in rx {
+            if event.is_ok() {
+                if let Err(error) = async_std::task::block_on(tera.write()).full_reload() {
+                    tide::log::error!("tera full_reload failed", { error: error.to_string() });
+                }
+            }
+        }
+    });
+}